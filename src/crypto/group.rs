@@ -0,0 +1,269 @@
+//! The group that ElGamal encryption and the Chaum-Pedersen/Schnorr
+//! proofs are computed over, abstracted behind [`GroupOps`] so the
+//! verifier can check records produced over either the original 3072-bit
+//! multiplicative group mod a safe prime, or the Ristretto255
+//! prime-order group over Curve25519 used by modern curve-based ElGamal
+//! implementations (e.g. `elastic-elgamal`'s `Generic<Group>`).
+//!
+//! [`Element`] and [`Exponent`] are the serializable, backend-tagged
+//! values that flow through [`crate::schema`]; [`GroupOps`] is the
+//! backend-specific arithmetic used to check proofs against them. The
+//! `GroupOps` methods that combine untrusted `Element`s and `Exponent`s
+//! (`mul`/`pow`) return `None` for mismatched backends or malformed
+//! encodings rather than panicking.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+/// A group element: an ElGamal ciphertext component, public key, or
+/// commitment. Tagged by the backend it was produced over.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Element {
+    /// An element of the multiplicative group mod `prime`.
+    ModP(#[serde(deserialize_with = "crate::deserialize::biguint")] BigUint),
+    /// The compressed encoding of a Ristretto255 point.
+    Ristretto255(#[serde(with = "hex32")] [u8; 32]),
+}
+
+impl Element {
+    /// Returns the underlying value if this is a `ModP` element, or
+    /// `None` if it is a `Ristretto255` element (e.g. a record mixing
+    /// backends).
+    pub fn as_modp(&self) -> Option<&BigUint> {
+        match self {
+            Element::ModP(value) => Some(value),
+            Element::Ristretto255(_) => None,
+        }
+    }
+}
+
+/// A scalar: a private key, proof response, or exponent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Exponent {
+    /// An exponent taken modulo the subgroup order `q`.
+    ModP(#[serde(deserialize_with = "crate::deserialize::biguint")] BigUint),
+    /// A Ristretto255 scalar, taken modulo the curve's prime order `l`.
+    Ristretto255(#[serde(with = "hex32")] [u8; 32]),
+}
+
+/// The group arithmetic a backend must provide to check ElGamal
+/// ciphertexts and Chaum-Pedersen/Schnorr proofs: a generator, order,
+/// and identity, multiplication and exponentiation of elements, and a
+/// Fiat-Shamir hash-to-scalar. `mul`/`pow` return `None` if passed an
+/// `Element`/`Exponent` from a different backend, or (for Ristretto255)
+/// an encoding that does not decompress to a valid point — both of
+/// which a malformed or backend-mismatched record can trigger, and
+/// neither of which may ever panic the verifier. The `ModP` backend
+/// accepts any integer and does not check subgroup membership.
+pub trait GroupOps {
+    fn generator(&self) -> Element;
+    fn identity(&self) -> Element;
+
+    /// The order of the group's scalar field, as an integer. This is
+    /// deliberately a `BigUint` rather than an `Exponent`: the order is
+    /// not itself a well-formed exponent (reducing it mod itself gives
+    /// zero), so callers that need to fold an arbitrary `BigUint`
+    /// exponent (e.g. a LaGrange/Feldman evaluation power) into this
+    /// backend's representation must go through `scalar_from_biguint`,
+    /// not construct an `Exponent` from `order()` directly.
+    fn order(&self) -> BigUint;
+
+    fn mul(&self, a: &Element, b: &Element) -> Option<Element>;
+    fn pow(&self, base: &Element, exponent: &Exponent) -> Option<Element>;
+
+    /// Reduces `value` modulo this backend's scalar field order and
+    /// encodes it as that backend's `Exponent` representation.
+    fn scalar_from_biguint(&self, value: &BigUint) -> Exponent;
+
+    fn hash_to_scalar(&self, bytes: &[u8]) -> Exponent;
+}
+
+/// The original mod-p backend: the multiplicative group of integers
+/// mod the safe prime `prime`, with subgroup order `subgroup_order`.
+pub struct ModP {
+    pub prime: BigUint,
+    pub subgroup_order: BigUint,
+    pub generator: BigUint,
+}
+
+impl GroupOps for ModP {
+    fn generator(&self) -> Element {
+        Element::ModP(self.generator.clone())
+    }
+
+    fn identity(&self) -> Element {
+        Element::ModP(BigUint::from(1u32))
+    }
+
+    fn order(&self) -> BigUint {
+        self.subgroup_order.clone()
+    }
+
+    fn mul(&self, a: &Element, b: &Element) -> Option<Element> {
+        let a = a.as_modp()?;
+        let b = b.as_modp()?;
+        Some(Element::ModP((a * b) % &self.prime))
+    }
+
+    fn pow(&self, base: &Element, exponent: &Exponent) -> Option<Element> {
+        let base = base.as_modp()?;
+        let exponent = match exponent {
+            Exponent::ModP(exponent) => exponent,
+            Exponent::Ristretto255(_) => return None,
+        };
+        Some(Element::ModP(base.modpow(exponent, &self.prime)))
+    }
+
+    fn scalar_from_biguint(&self, value: &BigUint) -> Exponent {
+        Exponent::ModP(value % &self.subgroup_order)
+    }
+
+    fn hash_to_scalar(&self, bytes: &[u8]) -> Exponent {
+        let digest = Sha256::digest(bytes);
+        Exponent::ModP(BigUint::from_bytes_be(&digest) % &self.subgroup_order)
+    }
+}
+
+/// The Ristretto255 backend: the prime-order group over Curve25519,
+/// selected for records produced with modern curve-based ElGamal.
+pub struct Ristretto255;
+
+impl GroupOps for Ristretto255 {
+    fn generator(&self) -> Element {
+        Element::Ristretto255(
+            curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT
+                .compress()
+                .to_bytes(),
+        )
+    }
+
+    fn identity(&self) -> Element {
+        Element::Ristretto255(curve25519_dalek::ristretto::RistrettoPoint::default().compress().to_bytes())
+    }
+
+    fn order(&self) -> BigUint {
+        // l = 2^252 + 27742317777372353535851937790883648493, the prime
+        // order of the Ristretto255 / Curve25519 basepoint subgroup.
+        BigUint::parse_bytes(b"1000000000000000000000000000000014DEF9DEA2F79CD65812631A5CF5D3ED", 16)
+            .expect("Ristretto255 order constant is valid hex")
+    }
+
+    fn mul(&self, a: &Element, b: &Element) -> Option<Element> {
+        let a = decompress(a)?;
+        let b = decompress(b)?;
+        Some(Element::Ristretto255((a + b).compress().to_bytes()))
+    }
+
+    fn pow(&self, base: &Element, exponent: &Exponent) -> Option<Element> {
+        let base = decompress(base)?;
+        let scalar = match exponent {
+            Exponent::Ristretto255(bytes) => curve25519_dalek::scalar::Scalar::from_bytes_mod_order(*bytes),
+            Exponent::ModP(_) => return None,
+        };
+        Some(Element::Ristretto255((base * scalar).compress().to_bytes()))
+    }
+
+    fn scalar_from_biguint(&self, value: &BigUint) -> Exponent {
+        let reduced = (value % self.order()).to_bytes_le();
+        let mut scalar = [0u8; 32];
+        scalar[..reduced.len()].copy_from_slice(&reduced);
+        Exponent::Ristretto255(scalar)
+    }
+
+    fn hash_to_scalar(&self, bytes: &[u8]) -> Exponent {
+        Exponent::Ristretto255(
+            curve25519_dalek::scalar::Scalar::hash_from_bytes::<Sha512>(bytes).to_bytes(),
+        )
+    }
+}
+
+fn decompress(element: &Element) -> Option<curve25519_dalek::ristretto::RistrettoPoint> {
+    match element {
+        Element::Ristretto255(bytes) => curve25519_dalek::ristretto::CompressedRistretto(*bytes).decompress(),
+        Element::ModP(_) => None,
+    }
+}
+
+/// Hex encoding for the fixed-size byte arrays backing `Ristretto255`
+/// elements and scalars.
+mod hex32 {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        hex::encode(bytes).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = hex::decode(&encoded).map_err(serde::de::Error::custom)?;
+        bytes
+            .try_into()
+            .map_err(|_| serde::de::Error::custom("expected 32 bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ristretto255_element_round_trips_through_json() {
+        let group = Ristretto255;
+        let element = group.generator();
+        let json = serde_json::to_string(&element).unwrap();
+        assert_eq!(serde_json::from_str::<Element>(&json).unwrap(), element);
+    }
+
+    #[test]
+    fn ristretto255_pow_matches_repeated_mul() {
+        let group = Ristretto255;
+        let generator = group.generator();
+
+        let three = group.scalar_from_biguint(&BigUint::from(3u32));
+        let cubed = group.pow(&generator, &three).unwrap();
+
+        let doubled = group.mul(&generator, &generator).unwrap();
+        let tripled = group.mul(&doubled, &generator).unwrap();
+
+        assert_eq!(cubed, tripled);
+    }
+
+    #[test]
+    fn ristretto255_pow_rejects_a_modp_exponent() {
+        let group = Ristretto255;
+        assert!(group.pow(&group.generator(), &Exponent::ModP(BigUint::from(3u32))).is_none());
+    }
+
+    #[test]
+    fn ristretto255_mul_rejects_a_modp_element() {
+        let group = Ristretto255;
+        assert!(group.mul(&group.generator(), &Element::ModP(BigUint::from(3u32))).is_none());
+    }
+
+    #[test]
+    fn ristretto255_scalar_from_biguint_reduces_mod_the_order() {
+        let group = Ristretto255;
+        let order = group.order();
+        assert_eq!(group.scalar_from_biguint(&order), group.scalar_from_biguint(&BigUint::from(0u32)));
+    }
+
+    #[test]
+    fn modp_pow_matches_repeated_mul() {
+        let group = ModP {
+            prime: BigUint::from(23u32),
+            subgroup_order: BigUint::from(11u32),
+            generator: BigUint::from(4u32),
+        };
+        let generator = group.generator();
+
+        let cubed = group.pow(&generator, &group.scalar_from_biguint(&BigUint::from(3u32))).unwrap();
+
+        let doubled = group.mul(&generator, &generator).unwrap();
+        let tripled = group.mul(&doubled, &generator).unwrap();
+
+        assert_eq!(cubed, tripled);
+    }
+}
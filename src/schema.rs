@@ -1,5 +1,7 @@
+use num::traits::{One, Zero};
 use num::BigUint;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::ballot;
 use crate::crypto::chaum_pederson;
@@ -7,8 +9,22 @@ use crate::crypto::elgamal;
 use crate::crypto::schnorr;
 use crate::crypto::group::{Element, Exponent};
 
+/// The cryptographic group that an election's ElGamal ciphertexts and
+/// Chaum-Pedersen/Schnorr proofs are computed over.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupBackend {
+    /// The original 3072-bit multiplicative group mod a safe prime.
+    /// The default, since every record published before this field
+    /// existed was mod-p.
+    #[default]
+    ModP,
+    /// The Ristretto255 prime-order group over Curve25519.
+    Ristretto255,
+}
+
 /// All the parameters necessary to form the election.
-#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, Debug, Serialize)]
 pub struct Parameters {
     /// The date on which the election takes place.
     pub date: String,
@@ -17,20 +33,100 @@ pub struct Parameters {
     pub location: String,
 
     /// The number of election trustees `n`.
-    #[serde(deserialize_with = "crate::deserialize::biguint")]
     pub num_trustees: BigUint,
 
     /// The threshold `k` of trustees required to complete
     /// verification.
-    #[serde(deserialize_with = "crate::deserialize::biguint")]
     pub threshold: BigUint,
 
     /// The prime modulus of the group used for encryption.
-    #[serde(deserialize_with = "crate::deserialize::biguint")]
     pub prime: BigUint,
 
+    /// The order `q` of the subgroup generated by `generator`, i.e. the
+    /// relevant prime factor of `prime - 1`. LaGrange coefficients and
+    /// other exponents are computed modulo this value.
+    pub subgroup_order: BigUint,
+
     /// The generator of the group used for encryption.
     pub generator: Element,
+
+    /// Which group backend `prime`, `subgroup_order`, and `generator`
+    /// (along with every other `Element`/`Exponent` in the record) are
+    /// interpreted under.
+    pub backend: GroupBackend,
+}
+
+impl<'de> Deserialize<'de> for Parameters {
+    /// Deserializes like a plain derive, except `subgroup_order` and
+    /// `backend` — both added after this schema's first release — are
+    /// optional: a record with neither is assumed to be the original
+    /// mod-p safe-prime group, where `subgroup_order = (prime - 1) / 2`.
+    /// A Ristretto255 record without `subgroup_order` takes the curve's
+    /// prime order `l`.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Raw {
+            date: String,
+            location: String,
+            #[serde(deserialize_with = "crate::deserialize::biguint")]
+            num_trustees: BigUint,
+            #[serde(deserialize_with = "crate::deserialize::biguint")]
+            threshold: BigUint,
+            #[serde(deserialize_with = "crate::deserialize::biguint")]
+            prime: BigUint,
+            #[serde(default, deserialize_with = "crate::deserialize::biguint")]
+            subgroup_order: BigUint,
+            generator: Element,
+            #[serde(default)]
+            backend: GroupBackend,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+
+        let subgroup_order = if !raw.subgroup_order.is_zero() {
+            raw.subgroup_order
+        } else {
+            match raw.backend {
+                GroupBackend::ModP => (&raw.prime - BigUint::one()) / BigUint::from(2u32),
+                GroupBackend::Ristretto255 => {
+                    crate::crypto::group::GroupOps::order(&crate::crypto::group::Ristretto255)
+                }
+            }
+        };
+
+        Ok(Parameters {
+            date: raw.date,
+            location: raw.location,
+            num_trustees: raw.num_trustees,
+            threshold: raw.threshold,
+            prime: raw.prime,
+            subgroup_order,
+            generator: raw.generator,
+            backend: raw.backend,
+        })
+    }
+}
+
+impl Parameters {
+    /// Returns the group arithmetic implementation selected by `backend`,
+    /// or `None` if `generator` does not actually match that backend
+    /// (a malformed record).
+    pub fn group(&self) -> Option<Box<dyn crate::crypto::group::GroupOps>> {
+        match self.backend {
+            GroupBackend::ModP => Some(Box::new(crate::crypto::group::ModP {
+                prime: self.prime.clone(),
+                subgroup_order: self.subgroup_order.clone(),
+                generator: self.generator.as_modp()?.clone(),
+            })),
+            GroupBackend::Ristretto255 => match self.generator {
+                Element::Ristretto255(_) => Some(Box::new(crate::crypto::group::Ristretto255)),
+                Element::ModP(_) => None,
+            },
+        }
+    }
 }
 
 /// All data from an ElectionGuard election
@@ -58,6 +154,12 @@ pub struct Record {
     /// The encrypted ballots cast in the election.
     pub cast_ballots: Vec<CastBallot>,
 
+    /// A Merkle tree over `cast_ballots`, letting a voter confirm their
+    /// ballot is included in the final tally set without revealing any
+    /// other ballot. Absent for records published before this was added.
+    #[serde(default)]
+    pub ballot_chain: Option<BallotChain>,
+
     /// The decryptions of the tallies of each option for each
     /// contests in the election.
     pub contest_tallies: Vec<ContestTally>,
@@ -94,7 +196,21 @@ pub struct TrusteeCoefficient {
 #[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct CastBallot {
     pub ballot_info: ballot::Information,
-    pub contests: Vec<CastContest>,
+    pub contests: Vec<Contest>,
+}
+
+/// A single contest on a cast ballot, either a traditional "choose at
+/// most `L`" contest or a quadratic-voting contest.
+///
+/// Untagged, and tried in this order, so that existing records (which
+/// serialize a contest as a bare `CastContest`, with no discriminant) keep
+/// deserializing unchanged: a `CastContest` has no `budget`/`budget_proof`
+/// fields, so it can never be mistaken for a `QuadraticContest`.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Contest {
+    Plurality(CastContest),
+    Quadratic(QuadraticContest),
 }
 
 /// A contests consists of a list of encrypted selections, along with
@@ -113,6 +229,90 @@ pub struct CastContest {
     pub num_selections_proof: chaum_pederson::Proof,
 }
 
+/// A quadratic-voting contest: the voter spreads a fixed credit
+/// `budget` across options, where placing `v` votes on an option costs
+/// `v^2` credits.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct QuadraticContest {
+    /// The encrypted vote count and cost for each option.
+    pub selections: Vec<QuadraticSelection>,
+
+    /// The credit budget `B` every voter is allotted to spread across
+    /// this contest's options.
+    #[serde(deserialize_with = "crate::deserialize::biguint")]
+    pub budget: BigUint,
+
+    /// Proof that the homomorphic product of the per-option cost
+    /// ciphertexts decrypts consistently with `budget`.
+    pub budget_proof: chaum_pederson::Proof,
+}
+
+impl QuadraticContest {
+    /// Verifies this contest: every option's range proof attests that
+    /// its vote count lies in `[0, max_votes]`, every option's square
+    /// proof ties its `cost` to the square of that same vote count, and
+    /// `budget_proof` ties the declared `budget` to the homomorphic
+    /// product of the per-option cost ciphertexts. All three are
+    /// required: without the square proof, a voter could report an
+    /// arbitrarily small `cost` for a large `vote_count` and still pass
+    /// the aggregate budget check.
+    pub fn verify(&self, base_hash: &BigUint, public_key: &Element) -> bool {
+        let selections_hold = self
+            .selections
+            .iter()
+            .all(|selection| selection.verify(base_hash, public_key));
+
+        let aggregate_cost = self
+            .selections
+            .iter()
+            .map(|selection| &selection.cost)
+            .fold(elgamal::Message::identity(), |acc, cost| acc.combine(cost));
+
+        selections_hold && self.budget_proof.verify(base_hash, public_key, &aggregate_cost)
+    }
+}
+
+/// One option within a `QuadraticContest`.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct QuadraticSelection {
+    /// The encryption of the number of votes `v` placed on this option.
+    pub vote_count: elgamal::Message,
+
+    /// The maximum number of votes that can be placed on a single
+    /// option.
+    #[serde(deserialize_with = "crate::deserialize::biguint")]
+    pub max_votes: BigUint,
+
+    /// Proof that `vote_count` encrypts some value in `[0, max_votes]`.
+    /// This is a distinct proof type from `CastSelection`'s binary
+    /// `disj::Proof` (which only ever disjuncts over zero and one): it
+    /// disjuncts over the `max_votes + 1` values a quadratic-voting
+    /// selection can take. Nested under its own key, rather than
+    /// flattened, so it can't collide with `square_proof`'s fields when
+    /// both appear on the same `QuadraticSelection`.
+    pub range_proof: chaum_pederson::range::Proof,
+
+    /// The encryption of this option's cost `v^2`.
+    pub cost: elgamal::Message,
+
+    /// Proof that `cost` encrypts the square of the value encrypted by
+    /// `vote_count`. Without this, `cost` is an unconstrained ciphertext
+    /// and a voter could place the maximum vote count while declaring an
+    /// arbitrarily small cost. Nested under its own key for the same
+    /// reason as `range_proof`.
+    pub square_proof: chaum_pederson::square::Proof,
+}
+
+impl QuadraticSelection {
+    /// Verifies the disjunctive range proof that `vote_count` encrypts
+    /// some value in `[0, max_votes]`, and the proof that `cost` encrypts
+    /// the square of that same value.
+    pub fn verify(&self, base_hash: &BigUint, public_key: &Element) -> bool {
+        self.range_proof.verify(base_hash, public_key, &self.vote_count, &self.max_votes)
+            && self.square_proof.verify(base_hash, public_key, &self.vote_count, &self.cost)
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct CastSelection {
     /// The value of this selection.  This is an encryption of either zero or one.
@@ -172,9 +372,8 @@ pub struct Share {
     /// encrypted message.
     pub proof: chaum_pederson::Proof,
 
-    /// The share of the decrypted message `M_i`.
-    #[serde(deserialize_with = "crate::deserialize::biguint")]
-    pub share: BigUint,
+    /// The share of the decrypted message `M_i`, a group element.
+    pub share: Element,
 }
 
 #[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -184,14 +383,56 @@ pub struct ShareRecovery {
     pub fragments: Vec<Fragment>,
 }
 
+impl ShareRecovery {
+    /// Verifies that the fragments used to reconstruct this share came
+    /// from a legitimate LaGrange interpolation of `missing_trustee`'s
+    /// polynomial: the contributing trustee indices are distinct, in
+    /// `[0, num_trustees)`, number exactly `threshold`, each fragment's
+    /// stored coefficient matches the value independently recomputed
+    /// from those indices, and each fragment is consistent with
+    /// `missing_trustee`'s published Feldman commitments.
+    ///
+    /// `base_hash` and `ciphertext` are the base hash and the encrypted
+    /// message this share is a partial decryption of (the containing
+    /// `DecryptedValue`'s `encrypted_value`).
+    pub fn verify(
+        &self,
+        parameters: &Parameters,
+        base_hash: &BigUint,
+        missing_trustee: &TrusteePublicKey,
+        ciphertext: &elgamal::Message,
+    ) -> bool {
+        let present: Vec<BigUint> = self
+            .fragments
+            .iter()
+            .map(|fragment| fragment.trustee_index.clone())
+            .collect();
+
+        let mut seen = std::collections::BTreeSet::new();
+        for index in &present {
+            if index >= &parameters.num_trustees || !seen.insert(index.clone()) {
+                return false;
+            }
+        }
+        if BigUint::from(present.len()) != parameters.threshold {
+            return false;
+        }
+
+        self.fragments.iter().all(|fragment| {
+            fragment.verify_lagrange_coefficient(parameters, &present)
+                && fragment.verify_feldman_commitment(parameters, base_hash, missing_trustee, ciphertext)
+        })
+    }
+}
+
 /// A fragment of a missing trustee's share of a decryption, including
 /// the LaGrange coefficient.
 #[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Fragment {
-    /// The actual fragment `M_{i,j}` which is trustee `j`'s piece of
-    /// the missing trustee `i`'s share of a decryption.
-    #[serde(deserialize_with = "crate::deserialize::biguint")]
-    pub fragment: BigUint,
+    /// The actual fragment `M_{i,j}`, a group element, which is
+    /// trustee `j`'s piece of the missing trustee `i`'s share of a
+    /// decryption.
+    pub fragment: Element,
 
     /// The LaGrange coefficient `w_{i,j}` used to compute the
     /// decryption share from the fragments.
@@ -206,3 +447,443 @@ pub struct Fragment {
     #[serde(deserialize_with = "crate::deserialize::biguint")]
     pub trustee_index: BigUint,
 }
+
+impl Fragment {
+    /// Recomputes the LaGrange coefficient `w_{i,j}` that interpolates
+    /// this fragment's contribution to the secret at `x = 0`, from the
+    /// 1-based evaluation points of `present` (the trustee indices that
+    /// actually contributed a fragment), and checks it against
+    /// `lagrange_coefficient`.
+    pub fn verify_lagrange_coefficient(&self, parameters: &Parameters, present: &[BigUint]) -> bool {
+        lagrange_coefficient(parameters, &self.trustee_index, present) == self.lagrange_coefficient
+    }
+
+    /// Checks this fragment as a Feldman VSS share. `fragment` itself is
+    /// `M_{i,j} = A^{P_i(x)}` (the ciphertext's first component raised
+    /// to the committing trustee's polynomial evaluated at
+    /// `x = trustee_index + 1`), not `g^{P_i(x)}` — so it cannot be
+    /// checked against the commitments directly. Instead this
+    /// recomputes the Feldman commitment element
+    /// `g^{P_i(x)} = ∏_j K_{i,j}^{x^j mod q}` and hands it, alongside
+    /// `ciphertext`, to this fragment's own Chaum-Pedersen `proof`,
+    /// which attests that `fragment` and the commitment element share
+    /// the same exponent `P_i(x)` in their respective bases `A` and `g`.
+    pub fn verify_feldman_commitment(
+        &self,
+        parameters: &Parameters,
+        base_hash: &BigUint,
+        committing_trustee: &TrusteePublicKey,
+        ciphertext: &elgamal::Message,
+    ) -> bool {
+        let commitments: Vec<Element> = committing_trustee
+            .coefficients
+            .iter()
+            .map(|coefficient| coefficient.public_key.clone())
+            .collect();
+
+        match feldman_commitment(parameters, &commitments, &self.trustee_index) {
+            Some(commitment) => self.proof.verify(base_hash, &commitment, ciphertext, &self.fragment),
+            None => false,
+        }
+    }
+}
+
+/// Recomputes the Feldman commitment element `g^{P_i(x)} = ∏_j
+/// K_{i,j}^{x^j mod q}` for the committing trustee's coefficient
+/// commitments `commitments = [K_{i,0}, …, K_{i,k-1}]`, at the 1-based
+/// evaluation point `x = index + 1`. Returns `None` if
+/// `parameters.group()` does (the generator doesn't match
+/// `parameters.backend`) or if any commitment doesn't match that
+/// backend.
+fn feldman_commitment(parameters: &Parameters, commitments: &[Element], index: &BigUint) -> Option<Element> {
+    let group = parameters.group()?;
+    let q = group.order();
+    let x = index + BigUint::one();
+
+    let mut commitment = group.identity();
+    let mut x_power = BigUint::one();
+    for public_key in commitments {
+        let term = group.pow(public_key, &group.scalar_from_biguint(&x_power))?;
+        commitment = group.mul(&commitment, &term)?;
+        x_power = (x_power * &x) % &q;
+    }
+
+    Some(commitment)
+}
+
+/// Recomputes the LaGrange coefficient `w_{i,j}` interpolating the
+/// fragment contributed at `trustee_index` to the secret at `x = 0`,
+/// from the 1-based evaluation points of `present` (the trustee
+/// indices that actually contributed a fragment).
+fn lagrange_coefficient(parameters: &Parameters, trustee_index: &BigUint, present: &[BigUint]) -> BigUint {
+    let q = &parameters.subgroup_order;
+    let x_j = trustee_index + BigUint::one();
+
+    let mut numerator = BigUint::one();
+    let mut denominator = BigUint::one();
+    for x_l in present.iter().map(|index| index + BigUint::one()) {
+        if x_l == x_j {
+            continue;
+        }
+        numerator = (numerator * &x_l) % q;
+        denominator = (denominator * sub_mod(&x_l, &x_j, q)) % q;
+    }
+
+    (numerator * mod_inverse(&denominator, q)) % q
+}
+
+/// Computes `(a - b) mod q` for `a, b < q`, without going through a
+/// signed integer type.
+fn sub_mod(a: &BigUint, b: &BigUint, q: &BigUint) -> BigUint {
+    if a >= b {
+        (a - b) % q
+    } else {
+        q - (b - a) % q
+    }
+}
+
+/// Computes the modular inverse of `a` modulo the prime `q` via Fermat's
+/// little theorem: `a^{-1} = a^{q-2} mod q`.
+fn mod_inverse(a: &BigUint, q: &BigUint) -> BigUint {
+    a.modpow(&(q - BigUint::one() - BigUint::one()), q)
+}
+
+/// A Merkle tree over `Record::cast_ballots`, giving voters a way to
+/// confirm their ballot was included in the final published set.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct BallotChain {
+    /// The root of the Merkle tree over `cast_ballots`, in order.
+    #[serde(deserialize_with = "crate::deserialize::hash")]
+    pub root: BigUint,
+}
+
+impl BallotChain {
+    /// Recomputes the Merkle root over `ballots` and checks that it
+    /// equals the published `root`.
+    pub fn verify(&self, ballots: &[CastBallot]) -> bool {
+        self.root == merkle_root(ballots)
+    }
+}
+
+/// One step of a Merkle audit path: a sibling ("aunt") hash and which
+/// side of the combination it sits on.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct MerkleAunt {
+    #[serde(deserialize_with = "crate::deserialize::hash")]
+    pub hash: BigUint,
+    pub on_right: bool,
+}
+
+/// A proof that the ballot at `leaf_index` is one of the leaves that
+/// was combined into a `BallotChain`'s root.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// The index of the ballot being proven, within `cast_ballots`.
+    pub leaf_index: usize,
+
+    /// The sibling hash at each level of the tree, from the leaf up to
+    /// the root.
+    pub aunts: Vec<MerkleAunt>,
+}
+
+impl InclusionProof {
+    /// Builds the inclusion proof for the ballot at `leaf_index` among
+    /// `ballots`.
+    pub fn build(ballots: &[CastBallot], leaf_index: usize) -> InclusionProof {
+        let leaves: Vec<BigUint> = ballots.iter().map(ballot_leaf_hash).collect();
+        build_inclusion_proof(&leaves, leaf_index)
+    }
+
+    /// Checks that `leaf`, combined with this proof's sibling hashes,
+    /// produces `root`.
+    pub fn verify(&self, leaf: &CastBallot, root: &BigUint) -> bool {
+        self.verify_leaf_hash(&ballot_leaf_hash(leaf), root)
+    }
+
+    fn verify_leaf_hash(&self, leaf_hash: &BigUint, root: &BigUint) -> bool {
+        let mut hash = leaf_hash.clone();
+        for aunt in &self.aunts {
+            hash = if aunt.on_right {
+                combine_hashes(&hash, &aunt.hash)
+            } else {
+                combine_hashes(&aunt.hash, &hash)
+            };
+        }
+        hash == *root
+    }
+}
+
+/// The leaf-hash-level core of [`InclusionProof::build`], split out so it
+/// can operate on (and be tested against) arbitrary leaf hashes, not just
+/// ones derived from a `CastBallot`.
+fn build_inclusion_proof(leaves: &[BigUint], leaf_index: usize) -> InclusionProof {
+    let mut level = leaves.to_vec();
+    let mut index = leaf_index;
+    let mut aunts = Vec::new();
+
+    while level.len() > 1 {
+        let sibling_index = if index % 2 == 0 { index + 1 } else { index - 1 };
+        let sibling = level.get(sibling_index).unwrap_or(&level[index]).clone();
+        aunts.push(MerkleAunt {
+            hash: sibling,
+            on_right: index % 2 == 0,
+        });
+
+        level = combine_level(&level);
+        index /= 2;
+    }
+
+    InclusionProof { leaf_index, aunts }
+}
+
+/// Computes the Merkle root over the leaves of `ballots`, where each
+/// leaf is the SHA-256 hash of that ballot's tracking info and
+/// ciphertexts. Nodes are combined pairwise up the tree; a level with
+/// an odd number of nodes duplicates its last node and hashes it with
+/// itself, as in a standard Merkle audit tree (see `combine_level`). A
+/// single leaf is itself the root.
+fn merkle_root(ballots: &[CastBallot]) -> BigUint {
+    let leaves: Vec<BigUint> = ballots.iter().map(ballot_leaf_hash).collect();
+    merkle_root_from_leaves(&leaves)
+}
+
+/// The leaf-hash-level core of [`merkle_root`], split out so it can
+/// operate on (and be tested against) arbitrary leaf hashes, not just
+/// ones derived from a `CastBallot`.
+fn merkle_root_from_leaves(leaves: &[BigUint]) -> BigUint {
+    let mut level = leaves.to_vec();
+    if level.is_empty() {
+        return BigUint::zero();
+    }
+    while level.len() > 1 {
+        level = combine_level(&level);
+    }
+    level.remove(0)
+}
+
+/// Combines one level of a Merkle tree into the level above it. A
+/// level with an odd number of nodes duplicates its last node, as in a
+/// standard Merkle audit tree.
+fn combine_level(level: &[BigUint]) -> Vec<BigUint> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => combine_hashes(left, right),
+            [only] => combine_hashes(only, only),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Domain separation tags distinguishing a leaf hash from an internal
+/// node hash, so the two can never collide even if a node's encoding
+/// happens to equal some leaf's serialized bytes.
+const MERKLE_LEAF_DOMAIN: u8 = 0x00;
+const MERKLE_NODE_DOMAIN: u8 = 0x01;
+
+/// Encodes a Merkle node hash as a fixed-width 32-byte big-endian
+/// array. A SHA-256 digest is always 32 bytes, but `BigUint::to_bytes_be`
+/// drops leading zero bytes, so without re-padding, two different
+/// `(left, right)` pairs could hash to the same byte string.
+fn merkle_hash_bytes(hash: &BigUint) -> [u8; 32] {
+    let bytes = hash.to_bytes_be();
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    padded
+}
+
+fn combine_hashes(left: &BigUint, right: &BigUint) -> BigUint {
+    let mut hasher = Sha256::new();
+    hasher.update([MERKLE_NODE_DOMAIN]);
+    hasher.update(merkle_hash_bytes(left));
+    hasher.update(merkle_hash_bytes(right));
+    BigUint::from_bytes_be(&hasher.finalize())
+}
+
+/// Hashes a cast ballot's tracking info and encrypted selections into a
+/// single Merkle leaf.
+fn ballot_leaf_hash(ballot: &CastBallot) -> BigUint {
+    let bytes = serde_json::to_vec(ballot).expect("CastBallot always serializes");
+    let mut hasher = Sha256::new();
+    hasher.update([MERKLE_LEAF_DOMAIN]);
+    hasher.update(&bytes);
+    BigUint::from_bytes_be(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small (insecure, for-test-only) mod-p group: `p = 23`,
+    /// `q = 11` (the prime factor of `p - 1 = 22`), `g = 4`, which has
+    /// order 11 mod 23.
+    fn small_parameters() -> Parameters {
+        Parameters {
+            date: "2026-01-01".to_string(),
+            location: "Testville".to_string(),
+            num_trustees: BigUint::from(5u32),
+            threshold: BigUint::from(3u32),
+            prime: BigUint::from(23u32),
+            subgroup_order: BigUint::from(11u32),
+            generator: Element::ModP(BigUint::from(4u32)),
+            backend: GroupBackend::ModP,
+        }
+    }
+
+    #[test]
+    fn parameters_defaults_subgroup_order_and_backend_for_legacy_records() {
+        // No `subgroup_order` or `backend` key, as published before
+        // either field existed.
+        let json = r#"{
+            "date": "2020-01-01",
+            "location": "Testville",
+            "num_trustees": "5",
+            "threshold": "3",
+            "prime": "23",
+            "generator": "4"
+        }"#;
+
+        let parameters: Parameters = serde_json::from_str(json).unwrap();
+        assert_eq!(parameters.backend, GroupBackend::ModP);
+        assert_eq!(parameters.subgroup_order, BigUint::from(11u32));
+    }
+
+    #[test]
+    fn feldman_commitment_matches_direct_computation() {
+        let parameters = small_parameters();
+        // K_{i,0} = g^3, K_{i,1} = g^5, K_{i,2} = g^2 (the committing
+        // trustee's coefficient commitments).
+        let coefficients = [3u32, 5u32, 2u32];
+        let commitments: Vec<Element> = coefficients
+            .iter()
+            .map(|a| Element::ModP(BigUint::from(4u32).modpow(&BigUint::from(*a), &BigUint::from(23u32))))
+            .collect();
+
+        // Contributing trustee index 1 -> evaluation point x = 2.
+        let index = BigUint::from(1u32);
+        let x = BigUint::from(2u32);
+        let expected = coefficients
+            .iter()
+            .enumerate()
+            .fold(BigUint::from(1u32), |acc, (j, a)| {
+                let power = x.modpow(&BigUint::from(j as u32), &BigUint::from(11u32));
+                (acc * BigUint::from(4u32).modpow(&(BigUint::from(*a) * power), &BigUint::from(23u32))) % BigUint::from(23u32)
+            });
+
+        let commitment = feldman_commitment(&parameters, &commitments, &index).unwrap();
+        assert_eq!(commitment, Element::ModP(expected));
+    }
+
+    fn ristretto_parameters() -> Parameters {
+        let mut parameters = small_parameters();
+        parameters.backend = GroupBackend::Ristretto255;
+        parameters.generator =
+            Element::Ristretto255(curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT.compress().to_bytes());
+        parameters
+    }
+
+    #[test]
+    fn feldman_commitment_works_over_ristretto255() {
+        let parameters = ristretto_parameters();
+        let group = parameters.group().unwrap();
+
+        // Same K_{i,0..2} = g^{3,5,2} commitments as the mod-p test
+        // above, computed over the curve instead.
+        let coefficients = [3u32, 5u32, 2u32];
+        let commitments: Vec<Element> = coefficients
+            .iter()
+            .map(|a| group.pow(&group.generator(), &group.scalar_from_biguint(&BigUint::from(*a))).unwrap())
+            .collect();
+
+        let index = BigUint::from(1u32);
+        let x = BigUint::from(2u32);
+        let expected = coefficients.iter().enumerate().fold(group.identity(), |acc, (j, a)| {
+            let power = x.modpow(&BigUint::from(j as u32), &group.order());
+            let term = group
+                .pow(&group.generator(), &group.scalar_from_biguint(&(BigUint::from(*a) * power)))
+                .unwrap();
+            group.mul(&acc, &term).unwrap()
+        });
+
+        let commitment = feldman_commitment(&parameters, &commitments, &index).unwrap();
+        assert_eq!(commitment, expected);
+    }
+
+    #[test]
+    fn feldman_commitment_rejects_mismatched_backend_elements() {
+        let parameters = ristretto_parameters();
+
+        // A ModP-tagged commitment can't be decompressed as a
+        // Ristretto255 point, so this must fail closed, not panic.
+        let commitments = vec![Element::ModP(BigUint::from(4u32))];
+        let index = BigUint::from(1u32);
+        assert!(feldman_commitment(&parameters, &commitments, &index).is_none());
+    }
+
+    #[test]
+    fn lagrange_coefficient_reconstructs_the_secret() {
+        let parameters = small_parameters();
+
+        // P(x) = 7 + 3x + 5x^2 mod 11, a degree-2 (threshold-3) sharing
+        // of the secret `7`. Trustee index `i` holds the share `P(i+1)`.
+        let shares = [4u32, 0, 6, 0, 4];
+        let present: Vec<BigUint> = [0u32, 2, 4].iter().map(|i| BigUint::from(*i)).collect();
+
+        let expected_coefficients = [(0u32, 6u32), (2, 7), (4, 10)];
+        let mut reconstructed = BigUint::from(0u32);
+        for (index, expected) in expected_coefficients {
+            let coefficient = lagrange_coefficient(&parameters, &BigUint::from(index), &present);
+            assert_eq!(coefficient, BigUint::from(expected));
+            reconstructed = (reconstructed + coefficient * BigUint::from(shares[index as usize])) % &parameters.subgroup_order;
+        }
+
+        assert_eq!(reconstructed, BigUint::from(7u32));
+    }
+
+    #[test]
+    fn lagrange_coefficient_is_one_for_a_single_contributor() {
+        let parameters = small_parameters();
+        let present = vec![BigUint::from(2u32)];
+        assert_eq!(
+            lagrange_coefficient(&parameters, &BigUint::from(2u32), &present),
+            BigUint::from(1u32)
+        );
+    }
+
+    fn leaves(values: &[u32]) -> Vec<BigUint> {
+        values.iter().map(|v| BigUint::from(*v)).collect()
+    }
+
+    #[test]
+    fn merkle_root_of_a_single_leaf_is_the_leaf_itself() {
+        let leaves = leaves(&[11]);
+        assert_eq!(merkle_root_from_leaves(&leaves), leaves[0]);
+    }
+
+    #[test]
+    fn merkle_root_of_an_odd_level_duplicates_the_last_node() {
+        let leaves = leaves(&[1, 2, 3]);
+        let expected = combine_hashes(&combine_hashes(&leaves[0], &leaves[1]), &combine_hashes(&leaves[2], &leaves[2]));
+        assert_eq!(merkle_root_from_leaves(&leaves), expected);
+    }
+
+    #[test]
+    fn inclusion_proof_round_trips_for_every_leaf_in_an_odd_tree() {
+        let leaves = leaves(&[1, 2, 3, 4, 5]);
+        let root = merkle_root_from_leaves(&leaves);
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = build_inclusion_proof(&leaves, index);
+            assert!(proof.verify_leaf_hash(leaf, &root));
+        }
+    }
+
+    #[test]
+    fn inclusion_proof_rejects_a_mismatched_leaf() {
+        let leaves = leaves(&[1, 2, 3, 4]);
+        let root = merkle_root_from_leaves(&leaves);
+
+        let proof = build_inclusion_proof(&leaves, 0);
+        assert!(!proof.verify_leaf_hash(&leaves[1], &root));
+    }
+}